@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+
+use crate::errors::WebdevError;
+
+use super::models::{PermissionLevel, SearchUserAccess, UserAccess};
+use super::schema::{role_access, user_access};
+
+/// The effective level of a single `user_access`/`role_access` row. A SQL
+/// `NULL` `permission_level` predates the typed enum (or is simply never
+/// set), and is treated as *unrestricted* rather than `PermissionLevel::None`
+/// — these legacy boolean-style grants keep passing every `min` check, the
+/// same as they did before `CheckAccess` understood levels. Use
+/// `PermissionLevel::None` explicitly to grant access with no level.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GrantLevel {
+    Unrestricted,
+    Level(PermissionLevel),
+}
+
+impl GrantLevel {
+    fn satisfies(self, min: Option<PermissionLevel>) -> bool {
+        match (self, min) {
+            (GrantLevel::Unrestricted, _) => true,
+            (GrantLevel::Level(_), None) => true,
+            (GrantLevel::Level(level), Some(min)) => level >= min,
+        }
+    }
+
+    fn best(self, other: GrantLevel) -> GrantLevel {
+        match (self, other) {
+            (GrantLevel::Unrestricted, _) | (_, GrantLevel::Unrestricted) => GrantLevel::Unrestricted,
+            (GrantLevel::Level(a), GrantLevel::Level(b)) => GrantLevel::Level(a.max(b)),
+        }
+    }
+}
+
+impl From<Option<PermissionLevel>> for GrantLevel {
+    fn from(level: Option<PermissionLevel>) -> GrantLevel {
+        match level {
+            Some(level) => GrantLevel::Level(level),
+            None => GrantLevel::Unrestricted,
+        }
+    }
+}
+
+/// The highest `GrantLevel` `user_id` holds for `access_id`, across both
+/// their direct `user_access` rows and any role they hold that grants that
+/// access via `role_access`. `None` means the user has no grant at all.
+fn effective_level(
+    conn: &PgConnection,
+    user_id: i64,
+    access_id: i64,
+) -> Result<Option<GrantLevel>, WebdevError> {
+    let direct = user_access::table
+        .filter(user_access::user_id.eq(user_id))
+        .filter(user_access::access_id.eq(access_id))
+        .select(user_access::permission_level)
+        .load::<Option<PermissionLevel>>(conn)?;
+
+    let via_role = user_access::table
+        .inner_join(role_access::table.on(user_access::role_id.eq(role_access::role_id.nullable())))
+        .filter(user_access::user_id.eq(user_id))
+        .filter(role_access::access_id.eq(access_id))
+        .select(role_access::permission_level)
+        .load::<Option<PermissionLevel>>(conn)?;
+
+    Ok(direct
+        .into_iter()
+        .chain(via_role.into_iter())
+        .map(GrantLevel::from)
+        .reduce(GrantLevel::best))
+}
+
+/// Resolves whether `user_id` holds `access_id` at least at `min` level
+/// (or at all, if `min` is `None`), directly or transitively through a role.
+pub fn check_access(
+    conn: &PgConnection,
+    user_id: i64,
+    access_id: i64,
+    min: Option<PermissionLevel>,
+) -> Result<bool, WebdevError> {
+    let level = effective_level(conn, user_id, access_id)?;
+
+    Ok(match level {
+        None => false,
+        Some(level) => level.satisfies(min),
+    })
+}
+
+/// Resolves `check_access` for every id in `access_ids` with two queries
+/// (direct grants, then role-inherited grants) however many ids are given,
+/// rather than one query per id. Any access_id the user doesn't hold at all
+/// maps to `false`.
+pub fn batch_check_access(
+    conn: &PgConnection,
+    user_id: i64,
+    access_ids: &[i64],
+    min: Option<PermissionLevel>,
+) -> Result<HashMap<i64, bool>, WebdevError> {
+    let mut best_level: HashMap<i64, GrantLevel> = HashMap::new();
+
+    let direct = user_access::table
+        .filter(user_access::user_id.eq(user_id))
+        .filter(user_access::access_id.eq_any(access_ids))
+        .select((user_access::access_id, user_access::permission_level))
+        .load::<(Option<i64>, Option<PermissionLevel>)>(conn)?;
+
+    let via_role = user_access::table
+        .inner_join(role_access::table.on(user_access::role_id.eq(role_access::role_id.nullable())))
+        .filter(user_access::user_id.eq(user_id))
+        .filter(role_access::access_id.eq_any(access_ids))
+        .select((role_access::access_id, role_access::permission_level))
+        .load::<(i64, Option<PermissionLevel>)>(conn)?;
+
+    for (access_id, level) in direct.into_iter().filter_map(|(id, level)| id.map(|id| (id, level))) {
+        let level = GrantLevel::from(level);
+        let entry = best_level.entry(access_id).or_insert(level);
+        *entry = entry.best(level);
+    }
+
+    for (access_id, level) in via_role {
+        let level = GrantLevel::from(level);
+        let entry = best_level.entry(access_id).or_insert(level);
+        *entry = entry.best(level);
+    }
+
+    Ok(access_ids
+        .iter()
+        .map(|access_id| {
+            let satisfied = best_level
+                .get(access_id)
+                .map(|level| level.satisfies(min))
+                .unwrap_or(false);
+
+            (*access_id, satisfied)
+        })
+        .collect())
+}
+
+/// Runs a `SearchUserAccess` filter, returning the page of matching rows
+/// along with the total row count across all pages (for `ManyUsersPaged`).
+pub fn search_user_access(
+    conn: &PgConnection,
+    search: &SearchUserAccess,
+) -> Result<(Vec<UserAccess>, i64), WebdevError> {
+    macro_rules! apply_filters {
+        ($query:expr) => {{
+            let mut query = $query;
+
+            if let crate::search::Search::Exact(id) = search.access_id {
+                query = query.filter(user_access::access_id.eq(id));
+            }
+
+            if let crate::search::Search::Exact(id) = search.user_id {
+                query = query.filter(user_access::user_id.eq(id));
+            }
+
+            match search.permission_level {
+                crate::search::NullableSearch::Exact(level) => {
+                    query = query.filter(user_access::permission_level.eq(level));
+                },
+                crate::search::NullableSearch::Null => {
+                    query = query.filter(user_access::permission_level.is_null());
+                },
+                crate::search::NullableSearch::NoSearch => {},
+            }
+
+            query
+        }};
+    }
+
+    let total = apply_filters!(user_access::table.into_boxed())
+        .count()
+        .get_result(conn)?;
+
+    let page = apply_filters!(user_access::table.into_boxed())
+        .limit(search.limit)
+        .offset(search.offset)
+        .load::<UserAccess>(conn)?;
+
+    Ok((page, total))
+}
+
+/// All `user_access` rows, direct or role-inherited, that grant `user_id`
+/// the given `access_id`. Useful for displaying *why* a user has an access.
+pub fn grants_for(
+    conn: &PgConnection,
+    user_id: i64,
+    access_id: i64,
+) -> Result<Vec<UserAccess>, WebdevError> {
+    let direct = user_access::table
+        .filter(user_access::user_id.eq(user_id))
+        .filter(user_access::access_id.eq(access_id))
+        .load::<UserAccess>(conn)?;
+
+    let via_role = user_access::table
+        .inner_join(role_access::table.on(user_access::role_id.eq(role_access::role_id.nullable())))
+        .filter(user_access::user_id.eq(user_id))
+        .filter(role_access::access_id.eq(access_id))
+        .select(user_access::all_columns)
+        .load::<UserAccess>(conn)?;
+
+    Ok(direct.into_iter().chain(via_role.into_iter()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::establish_connection;
+    use crate::access::models::{NewRole, NewRoleAccess, NewUserAccess};
+    use crate::access::schema::{access, roles};
+    use diesel::insert_into;
+
+    #[test]
+    fn check_access_direct_grant() {
+        let conn = establish_connection();
+
+        conn.test_transaction::<_, diesel::result::Error, _>(|| {
+            let access_id: i64 = insert_into(access::table)
+                .values(access::access_name.eq("direct-test-access"))
+                .returning(access::id)
+                .get_result(&conn)?;
+
+            insert_into(user_access::table)
+                .values(NewUserAccess {
+                    access_id: Some(access_id),
+                    role_id: None,
+                    user_id: 1,
+                    permission_level: None,
+                })
+                .execute(&conn)?;
+
+            assert!(check_access(&conn, 1, access_id, None).unwrap());
+            assert!(!check_access(&conn, 2, access_id, None).unwrap());
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn check_access_respects_minimum_level() {
+        let conn = establish_connection();
+
+        conn.test_transaction::<_, diesel::result::Error, _>(|| {
+            let access_id: i64 = insert_into(access::table)
+                .values(access::access_name.eq("min-level-test-access"))
+                .returning(access::id)
+                .get_result(&conn)?;
+
+            insert_into(user_access::table)
+                .values(NewUserAccess {
+                    access_id: Some(access_id),
+                    role_id: None,
+                    user_id: 1,
+                    permission_level: Some(PermissionLevel::Write),
+                })
+                .execute(&conn)?;
+
+            assert!(check_access(&conn, 1, access_id, Some(PermissionLevel::Read)).unwrap());
+            assert!(check_access(&conn, 1, access_id, Some(PermissionLevel::Write)).unwrap());
+            assert!(!check_access(&conn, 1, access_id, Some(PermissionLevel::Admin)).unwrap());
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn check_access_treats_null_level_as_unrestricted() {
+        let conn = establish_connection();
+
+        conn.test_transaction::<_, diesel::result::Error, _>(|| {
+            let access_id: i64 = insert_into(access::table)
+                .values(access::access_name.eq("legacy-grant-test-access"))
+                .returning(access::id)
+                .get_result(&conn)?;
+
+            // Simulates a pre-enum grant carried over by the 000001 migration:
+            // no permission_level was ever recorded for this row.
+            insert_into(user_access::table)
+                .values(NewUserAccess {
+                    access_id: Some(access_id),
+                    role_id: None,
+                    user_id: 1,
+                    permission_level: None,
+                })
+                .execute(&conn)?;
+
+            assert!(check_access(&conn, 1, access_id, None).unwrap());
+            assert!(check_access(&conn, 1, access_id, Some(PermissionLevel::Admin)).unwrap());
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn batch_check_access_resolves_each_id_independently() {
+        let conn = establish_connection();
+
+        conn.test_transaction::<_, diesel::result::Error, _>(|| {
+            let granted_id: i64 = insert_into(access::table)
+                .values(access::access_name.eq("batch-test-granted"))
+                .returning(access::id)
+                .get_result(&conn)?;
+
+            let ungranted_id: i64 = insert_into(access::table)
+                .values(access::access_name.eq("batch-test-ungranted"))
+                .returning(access::id)
+                .get_result(&conn)?;
+
+            insert_into(user_access::table)
+                .values(NewUserAccess {
+                    access_id: Some(granted_id),
+                    role_id: None,
+                    user_id: 1,
+                    permission_level: None,
+                })
+                .execute(&conn)?;
+
+            let states = batch_check_access(&conn, 1, &[granted_id, ungranted_id], None).unwrap();
+
+            assert_eq!(states.get(&granted_id), Some(&true));
+            assert_eq!(states.get(&ungranted_id), Some(&false));
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn check_access_role_inherited_grant() {
+        let conn = establish_connection();
+
+        conn.test_transaction::<_, diesel::result::Error, _>(|| {
+            let access_id: i64 = insert_into(access::table)
+                .values(access::access_name.eq("role-test-access"))
+                .returning(access::id)
+                .get_result(&conn)?;
+
+            let role_id: i64 = insert_into(roles::table)
+                .values(NewRole { role_name: "editor".to_string() })
+                .returning(roles::id)
+                .get_result(&conn)?;
+
+            insert_into(role_access::table)
+                .values(NewRoleAccess {
+                    role_id,
+                    access_id,
+                    permission_level: None,
+                })
+                .execute(&conn)?;
+
+            insert_into(user_access::table)
+                .values(NewUserAccess {
+                    access_id: None,
+                    role_id: Some(role_id),
+                    user_id: 1,
+                    permission_level: None,
+                })
+                .execute(&conn)?;
+
+            assert!(check_access(&conn, 1, access_id, None).unwrap());
+            assert!(!check_access(&conn, 2, access_id, None).unwrap());
+
+            Ok(())
+        });
+    }
+}
@@ -1,4 +1,5 @@
 use diesel::Queryable;
+use diesel::sql_types::SmallInt;
 
 use rouille::router;
 
@@ -9,32 +10,35 @@ use url::form_urlencoded;
 
 use log::{trace, warn};
 
+use schemars::JsonSchema;
+
 use crate::errors::{WebdevError, WebdevErrorKind};
 
 use crate::search::{NullableSearch, Search};
 
 use crate::users::models::UserList;
-use super::schema::{access, user_access};
+use super::schema::{access, roles, role_access, user_access};
 
-#[derive(Queryable, Serialize, Deserialize)]
+#[derive(Queryable, Serialize, Deserialize, JsonSchema)]
 pub struct Access {
     pub id: i64,
     pub access_name: String,
 }
 
-#[derive(Insertable, Serialize, Deserialize)]
+#[derive(Insertable, Serialize, Deserialize, JsonSchema)]
 #[table_name = "access"]
 pub struct NewAccess {
     pub access_name: String,
 }
 
-#[derive(AsChangeset, Serialize, Deserialize)]
+#[derive(AsChangeset, Serialize, Deserialize, JsonSchema)]
 #[table_name = "access"]
 pub struct PartialAccess {
     pub access_name: String,
 }
 
 pub enum AccessRequest {
+    GetOpenApiSchema, //assembled OpenAPI document for this module
     GetAccess(i64), //id of access name searched
     CreateAccess(NewAccess), //new access type of some name to be created
     UpdateAccess(i64, PartialAccess), //Contains id to be changed to new access_name
@@ -46,6 +50,10 @@ impl AccessRequest {
         trace!("Creating AccessRequest from {:#?}", request);
 
         router!(request,
+            (GET) (/openapi.json) => {
+                Ok(AccessRequest::GetOpenApiSchema)
+            },
+
             (GET) (/{id: i64}) => {
                 Ok(AccessRequest::GetAccess(id))
             },
@@ -78,6 +86,7 @@ impl AccessRequest {
 }
 
 pub enum AccessResponse {
+    OpenApiSchema(serde_json::Value),
     OneAccess(Access),
     NoResponse,
 }
@@ -85,6 +94,7 @@ pub enum AccessResponse {
 impl AccessResponse {
     pub fn to_rouille(self) -> rouille::Response {
         match self {
+            AccessResponse::OpenApiSchema(schema) => rouille::Response::json(&schema),
             AccessResponse::OneAccess(access) => rouille::Response::json(&access),
             AccessResponse::NoResponse => rouille::Response::empty_204(),
         }
@@ -93,39 +103,211 @@ impl AccessResponse {
 
 
 
-#[derive(Queryable, Serialize, Deserialize)]
+#[derive(Queryable, Serialize, Deserialize, JsonSchema)]
+pub struct Role {
+    pub id: i64,
+    pub role_name: String,
+}
+
+#[derive(Insertable, Serialize, Deserialize, JsonSchema)]
+#[table_name = "roles"]
+pub struct NewRole {
+    pub role_name: String,
+}
+
+#[derive(AsChangeset, Serialize, Deserialize, JsonSchema)]
+#[table_name = "roles"]
+pub struct PartialRole {
+    pub role_name: String,
+}
+
+pub enum RoleRequest {
+    GetRole(i64), //id of role searched
+    CreateRole(NewRole), //new role of some name to be created
+    UpdateRole(i64, PartialRole), //contains id to be changed to new role_name
+    DeleteRole(i64), //id of role to be deleted
+}
+
+impl RoleRequest {
+    pub fn from_rouille(request: &rouille::Request) -> Result<RoleRequest, WebdevError> {
+        trace!("Creating RoleRequest from {:#?}", request);
+
+        router!(request,
+            (GET) (/{id: i64}) => {
+                Ok(RoleRequest::GetRole(id))
+            },
+
+            (POST) (/) => {
+                let request_body = request.data().ok_or(WebdevError::new(WebdevErrorKind::Format))?;
+                let new_role: NewRole = serde_json::from_reader(request_body)?;
+
+                Ok(RoleRequest::CreateRole(new_role))
+            },
+
+            (POST) (/{id: i64}) => {
+                let request_body = request.data().ok_or(WebdevError::new(WebdevErrorKind::Format))?;
+                let update_role: PartialRole = serde_json::from_reader(request_body)?;
+
+                Ok(RoleRequest::UpdateRole(id, update_role))
+            },
+
+            (DELETE) (/{id: i64}) => {
+                Ok(RoleRequest::DeleteRole(id))
+            },
+
+            _ => {
+                warn!("Could not create a role request for the given rouille request");
+                Err(WebdevError::new(WebdevErrorKind::NotFound))
+            }
+        ) //end router
+
+    }
+}
+
+pub enum RoleResponse {
+    OneRole(Role),
+    NoResponse,
+}
+
+impl RoleResponse {
+    pub fn to_rouille(self) -> rouille::Response {
+        match self {
+            RoleResponse::OneRole(role) => rouille::Response::json(&role),
+            RoleResponse::NoResponse => rouille::Response::empty_204(),
+        }
+    }
+}
+
+// Ordered so that `derive(PartialOrd, Ord)` gives the hierarchy
+// None < Read < Write < Admin, letting CheckAccess ask for a minimum
+// level rather than just a boolean grant.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, AsExpression, FromSqlRow, Serialize, Deserialize, JsonSchema)]
+#[sql_type = "SmallInt"]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionLevel {
+    None = 0,
+    Read = 1,
+    Write = 2,
+    Admin = 3,
+}
+
+impl PermissionLevel {
+    fn from_i16(value: i16) -> Result<PermissionLevel, String> {
+        match value {
+            0 => Ok(PermissionLevel::None),
+            1 => Ok(PermissionLevel::Read),
+            2 => Ok(PermissionLevel::Write),
+            3 => Ok(PermissionLevel::Admin),
+            _ => Err(format!("{} is not a valid PermissionLevel", value)),
+        }
+    }
+}
+
+impl<DB> diesel::serialize::ToSql<SmallInt, DB> for PermissionLevel
+where
+    DB: diesel::backend::Backend,
+    i16: diesel::serialize::ToSql<SmallInt, DB>,
+{
+    fn to_sql<W: std::io::Write>(&self, out: &mut diesel::serialize::Output<W, DB>) -> diesel::serialize::Result {
+        (*self as i16).to_sql(out)
+    }
+}
+
+impl std::str::FromStr for PermissionLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<PermissionLevel, String> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(PermissionLevel::None),
+            "read" => Ok(PermissionLevel::Read),
+            "write" => Ok(PermissionLevel::Write),
+            "admin" => Ok(PermissionLevel::Admin),
+            _ => Err(format!("{} is not a valid PermissionLevel", s)),
+        }
+    }
+}
+
+impl<DB> diesel::deserialize::FromSql<SmallInt, DB> for PermissionLevel
+where
+    DB: diesel::backend::Backend,
+    i16: diesel::deserialize::FromSql<SmallInt, DB>,
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> diesel::deserialize::Result<Self> {
+        let value = i16::from_sql(bytes)?;
+        PermissionLevel::from_i16(value).map_err(|e| e.into())
+    }
+}
+
+// Grants an access to every user who holds a given role. A user's
+// effective accesses are the union of their direct user_access rows
+// and the accesses granted by every role they hold (see CheckAccess).
+#[derive(Queryable, Serialize, Deserialize, JsonSchema)]
+pub struct RoleAccess {
+    pub id: i64,
+    pub role_id: i64,
+    pub access_id: i64,
+    pub permission_level: Option<PermissionLevel>,
+}
+
+#[derive(Insertable, Serialize, Deserialize, JsonSchema)]
+#[table_name = "role_access"]
+pub struct NewRoleAccess {
+    pub role_id: i64,
+    pub access_id: i64,
+    pub permission_level: Option<PermissionLevel>,
+}
+
+#[derive(Queryable, Serialize, Deserialize, JsonSchema)]
 pub struct UserAccess {
     pub permission_id: i64,
-    pub access_id: i64,
+    pub access_id: Option<i64>,
+    pub role_id: Option<i64>,
     pub user_id: i64,
-    pub permission_level: Option<String>,
+    pub permission_level: Option<PermissionLevel>,
 }
 
-#[derive(Insertable, Serialize, Deserialize)]
+#[derive(Insertable, Serialize, Deserialize, JsonSchema)]
 #[table_name = "user_access"]
 pub struct NewUserAccess {
-    pub access_id: i64,
+    pub access_id: Option<i64>,
+    pub role_id: Option<i64>,
     pub user_id: i64,
-    pub permission_level: Option<String>,
+    pub permission_level: Option<PermissionLevel>,
 }
 
-#[derive(AsChangeset, Serialize, Deserialize)]
+#[derive(AsChangeset, Serialize, Deserialize, JsonSchema)]
 #[table_name = "user_access"]
 pub struct PartialUserAccess {
-    pub access_id: i64,
+    pub access_id: Option<i64>,
+    pub role_id: Option<i64>,
     pub user_id: i64,
-    pub permission_level: Option<Option<String>>,
+    pub permission_level: Option<Option<PermissionLevel>>,
 }
 
+// Bounds how many rows a single search response can hand back, regardless
+// of what the caller asks for in `limit`.
+pub const MAX_USER_ACCESS_PAGE_SIZE: i64 = 100;
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct SearchUserAccess {
     pub access_id: Search<i64>,
     pub user_id: Search<i64>,
-    pub permission_level: NullableSearch<String>,
+    pub permission_level: NullableSearch<PermissionLevel>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct BatchCheckAccess {
+    pub user_id: i64,
+    pub access_ids: Vec<i64>,
+    pub min: Option<PermissionLevel>, //minimum level required of every access_id in the batch
 }
 
 pub enum UserAccessRequest {
     SearchAccess(SearchUserAccess), //list of users with access id or (?) name
-    CheckAccess(i64, i64), //entry allowing user of user_id to perform action of action_id
+    CheckAccess(i64, i64, Option<PermissionLevel>), //whether user_id holds at least `min` of access_id, directly or through a role
+    BatchCheckAccess(BatchCheckAccess), //whether user_id holds at least `min` of each access_id, in one request
     CreateAccess(NewUserAccess), //entry to add to database
     UpdateAccess(i64, PartialUserAccess), //entry to update with new information
     DeleteAccess(i64), //entry to delete from database
@@ -143,12 +325,28 @@ impl UserAccessRequest {
                 let mut access_id = Search::NoSearch;
                 let mut user_id = Search::NoSearch;
                 let mut permission_level = NullableSearch::NoSearch;
+                let mut limit = MAX_USER_ACCESS_PAGE_SIZE;
+                let mut offset = 0;
 
                 for (field, query) in url_queries {
                     match field.as_ref() {
                         "access_id" => access_id = Search::from_query(query.as_ref())?,
                         "user_id" => user_id = Search::from_query(query.as_ref())?,
                         "permission_level" => permission_level = NullableSearch::from_query(query.as_ref())?,
+                        "limit" => {
+                            limit = query.as_ref().parse::<i64>().map_err(|_| WebdevError::new(WebdevErrorKind::Format))?;
+
+                            if limit < 0 || limit > MAX_USER_ACCESS_PAGE_SIZE {
+                                return Err(WebdevError::new(WebdevErrorKind::Format));
+                            }
+                        },
+                        "offset" => {
+                            offset = query.as_ref().parse::<i64>().map_err(|_| WebdevError::new(WebdevErrorKind::Format))?;
+
+                            if offset < 0 {
+                                return Err(WebdevError::new(WebdevErrorKind::Format));
+                            }
+                        },
                         _ => return Err(WebdevError::new(WebdevErrorKind::Format)),
                     }
                 }
@@ -157,11 +355,29 @@ impl UserAccessRequest {
                     access_id,
                     user_id,
                     permission_level,
+                    limit,
+                    offset,
                 }))
             },
 
             (GET) (/{user_id:i64}/{access_id: i64}) => {
-                Ok(UserAccessRequest::CheckAccess(user_id, access_id))
+                let mut min = None;
+
+                for (field, query) in url_queries {
+                    match field.as_ref() {
+                        "min" => min = Some(query.as_ref().parse::<PermissionLevel>().map_err(|_| WebdevError::new(WebdevErrorKind::Format))?),
+                        _ => return Err(WebdevError::new(WebdevErrorKind::Format)),
+                    }
+                }
+
+                Ok(UserAccessRequest::CheckAccess(user_id, access_id, min))
+            },
+
+            (POST) (/check) => {
+                let request_body = request.data().ok_or(WebdevError::new(WebdevErrorKind::Format))?;
+                let batch_check: BatchCheckAccess = serde_json::from_reader(request_body)?;
+
+                Ok(UserAccessRequest::BatchCheckAccess(batch_check))
             },
 
             (POST) (/) => {
@@ -192,7 +408,9 @@ impl UserAccessRequest {
 
 pub enum UserAccessResponse {
     ManyUsers(UserList),
+    ManyUsersPaged { users: UserList, total: i64, limit: i64, offset: i64 },
     AccessState(bool),
+    AccessStates(std::collections::HashMap<i64, bool>),
     OneUserAccess(UserAccess),
     NoResponse,
 }
@@ -201,7 +419,14 @@ impl UserAccessResponse {
     pub fn to_rouille(self) -> rouille::Response {
         match self {
             UserAccessResponse::ManyUsers(users) => rouille::Response::json(&users),
+            UserAccessResponse::ManyUsersPaged { users, total, limit, offset } => rouille::Response::json(&serde_json::json!({
+                "users": users,
+                "total": total,
+                "limit": limit,
+                "offset": offset,
+            })),
             UserAccessResponse::AccessState(state) => rouille::Response::text(if state {"true"} else {"false"}),
+            UserAccessResponse::AccessStates(states) => rouille::Response::json(&states),
             UserAccessResponse::OneUserAccess(user_access) => rouille::Response::json(&user_access),
             UserAccessResponse::NoResponse => rouille::Response::empty_204(),
         }
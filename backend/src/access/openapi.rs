@@ -0,0 +1,198 @@
+//! Assembles a minimal OpenAPI 3.0 document describing the `access` and
+//! `user_access` routes, served from `GET /access/openapi.json`. Schemas are
+//! derived straight from the request/response types via `schemars`, so the
+//! document stays in sync with the models without hand-written docs.
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+use super::models::{
+    Access, BatchCheckAccess, NewAccess, NewRole, NewRoleAccess, NewUserAccess, PartialAccess,
+    PartialRole, PartialUserAccess, PermissionLevel, Role, RoleAccess, SearchUserAccess,
+    UserAccess,
+};
+
+struct ParamDoc {
+    name: &'static str,
+    location: &'static str, // "path" or "query"
+    required: bool,
+    schema_type: &'static str,
+}
+
+const PATH_ID: ParamDoc = ParamDoc { name: "id", location: "path", required: true, schema_type: "integer" };
+const PATH_USER_ID: ParamDoc = ParamDoc { name: "user_id", location: "path", required: true, schema_type: "integer" };
+const PATH_ACCESS_ID: ParamDoc = ParamDoc { name: "access_id", location: "path", required: true, schema_type: "integer" };
+const QUERY_MIN: ParamDoc = ParamDoc { name: "min", location: "query", required: false, schema_type: "string" };
+
+const SEARCH_QUERY_PARAMS: &[ParamDoc] = &[
+    ParamDoc { name: "access_id", location: "query", required: false, schema_type: "integer" },
+    ParamDoc { name: "user_id", location: "query", required: false, schema_type: "integer" },
+    ParamDoc { name: "permission_level", location: "query", required: false, schema_type: "string" },
+    ParamDoc { name: "limit", location: "query", required: false, schema_type: "integer" },
+    ParamDoc { name: "offset", location: "query", required: false, schema_type: "integer" },
+];
+
+struct RouteDoc {
+    method: &'static str,
+    path: &'static str,
+    summary: &'static str,
+    params: &'static [ParamDoc],
+    request_schema: Option<&'static str>,
+    response_schema: Option<&'static str>,
+}
+
+const ACCESS_ROUTES: &[RouteDoc] = &[
+    RouteDoc { method: "get", path: "/access/{id}", summary: "Get an access by id", params: &[PATH_ID], request_schema: None, response_schema: Some("Access") },
+    RouteDoc { method: "post", path: "/access", summary: "Create a new access", params: &[], request_schema: Some("NewAccess"), response_schema: Some("Access") },
+    RouteDoc { method: "post", path: "/access/{id}", summary: "Update an access", params: &[PATH_ID], request_schema: Some("PartialAccess"), response_schema: Some("Access") },
+    RouteDoc { method: "delete", path: "/access/{id}", summary: "Delete an access", params: &[PATH_ID], request_schema: None, response_schema: None },
+    RouteDoc { method: "get", path: "/role/{id}", summary: "Get a role by id", params: &[PATH_ID], request_schema: None, response_schema: Some("Role") },
+    RouteDoc { method: "post", path: "/role", summary: "Create a new role", params: &[], request_schema: Some("NewRole"), response_schema: Some("Role") },
+    RouteDoc { method: "post", path: "/role/{id}", summary: "Update a role", params: &[PATH_ID], request_schema: Some("PartialRole"), response_schema: Some("Role") },
+    RouteDoc { method: "delete", path: "/role/{id}", summary: "Delete a role", params: &[PATH_ID], request_schema: None, response_schema: None },
+    RouteDoc {
+        method: "get",
+        path: "/user_access",
+        summary: "Search user_access rows by access_id, user_id and/or permission_level, paginated by limit/offset; responds with { users, total, limit, offset }",
+        params: SEARCH_QUERY_PARAMS,
+        request_schema: None,
+        response_schema: None,
+    },
+    RouteDoc {
+        method: "get",
+        path: "/user_access/{user_id}/{access_id}",
+        summary: "Check whether user_id holds at least `min` of access_id",
+        params: &[PATH_USER_ID, PATH_ACCESS_ID, QUERY_MIN],
+        request_schema: None,
+        response_schema: None,
+    },
+    RouteDoc {
+        method: "post",
+        path: "/user_access/check",
+        summary: "Check whether user_id holds at least `min` of each access_id in one request",
+        params: &[],
+        request_schema: Some("BatchCheckAccess"),
+        response_schema: None,
+    },
+    RouteDoc { method: "post", path: "/user_access", summary: "Create a new user_access grant", params: &[], request_schema: Some("NewUserAccess"), response_schema: Some("UserAccess") },
+    RouteDoc { method: "post", path: "/user_access/{id}", summary: "Update a user_access grant", params: &[PATH_ID], request_schema: Some("PartialUserAccess"), response_schema: Some("UserAccess") },
+    RouteDoc { method: "delete", path: "/user_access/{id}", summary: "Delete a user_access grant", params: &[PATH_ID], request_schema: None, response_schema: None },
+];
+
+fn param_doc(param: &ParamDoc) -> Value {
+    json!({
+        "name": param.name,
+        "in": param.location,
+        "required": param.required,
+        "schema": { "type": param.schema_type }
+    })
+}
+
+fn operation_doc(route: &RouteDoc) -> Value {
+    let mut operation = Map::new();
+
+    operation.insert("summary".to_string(), json!(route.summary));
+
+    if !route.params.is_empty() {
+        operation.insert(
+            "parameters".to_string(),
+            Value::Array(route.params.iter().map(param_doc).collect()),
+        );
+    }
+
+    if let Some(schema) = route.request_schema {
+        operation.insert(
+            "requestBody".to_string(),
+            json!({
+                "required": true,
+                "content": {
+                    "application/json": {
+                        "schema": { "$ref": format!("#/components/schemas/{}", schema) }
+                    }
+                }
+            }),
+        );
+    }
+
+    let mut ok_response = Map::new();
+    ok_response.insert("description".to_string(), json!("Success"));
+
+    if let Some(schema) = route.response_schema {
+        ok_response.insert(
+            "content".to_string(),
+            json!({
+                "application/json": {
+                    "schema": { "$ref": format!("#/components/schemas/{}", schema) }
+                }
+            }),
+        );
+    }
+
+    operation.insert("responses".to_string(), json!({ "200": ok_response }));
+
+    Value::Object(operation)
+}
+
+/// Generates every type's schema through one `SchemaGenerator` configured
+/// for OpenAPI 3 (`$ref: #/components/schemas/<T>` instead of schemars'
+/// default draft-07 `#/definitions/<T>`), so nested types like
+/// `PermissionLevel` or `Search<T>` resolve against the document root
+/// rather than a local, non-existent `definitions` map.
+fn component_schemas() -> Map<String, Value> {
+    let mut generator = SchemaGenerator::new(SchemaSettings::openapi3());
+
+    macro_rules! add {
+        ($($ty:ty),* $(,)?) => {
+            $(generator.subschema_for::<$ty>();)*
+        };
+    }
+
+    add!(
+        Access,
+        NewAccess,
+        PartialAccess,
+        Role,
+        NewRole,
+        PartialRole,
+        RoleAccess,
+        NewRoleAccess,
+        UserAccess,
+        NewUserAccess,
+        PartialUserAccess,
+        SearchUserAccess,
+        PermissionLevel,
+        BatchCheckAccess,
+    );
+
+    generator
+        .definitions()
+        .iter()
+        .map(|(name, schema)| (name.clone(), json!(schema)))
+        .collect()
+}
+
+/// Builds the full OpenAPI document for this module: one `paths` entry per
+/// `AccessRequest`/`RoleRequest`/`UserAccessRequest` variant (except
+/// `AccessRequest::GetOpenApiSchema`, which is not listed), with its query
+/// parameters and request/response bodies `$ref`-ing `components.schemas`.
+pub fn document() -> Value {
+    let mut paths = Map::new();
+
+    for route in ACCESS_ROUTES {
+        let entry = paths.entry(route.path.to_string()).or_insert_with(|| json!({}));
+        entry[route.method] = operation_doc(route);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "access API",
+            "version": "1.0.0"
+        },
+        "paths": Value::Object(paths),
+        "components": {
+            "schemas": component_schemas()
+        }
+    })
+}
@@ -0,0 +1,39 @@
+table! {
+    access (id) {
+        id -> Bigint,
+        access_name -> Text,
+    }
+}
+
+table! {
+    roles (id) {
+        id -> Bigint,
+        role_name -> Text,
+    }
+}
+
+table! {
+    role_access (id) {
+        id -> Bigint,
+        role_id -> Bigint,
+        access_id -> Bigint,
+        permission_level -> Nullable<SmallInt>,
+    }
+}
+
+table! {
+    user_access (permission_id) {
+        permission_id -> Bigint,
+        access_id -> Nullable<Bigint>,
+        role_id -> Nullable<Bigint>,
+        user_id -> Bigint,
+        permission_level -> Nullable<SmallInt>,
+    }
+}
+
+joinable!(role_access -> roles (role_id));
+joinable!(role_access -> access (access_id));
+joinable!(user_access -> roles (role_id));
+joinable!(user_access -> access (access_id));
+
+allow_tables_to_appear_in_same_query!(access, roles, role_access, user_access);
@@ -0,0 +1,53 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WebdevErrorKind {
+    NotFound,
+    Format,
+    Forbidden,
+    DatabaseError,
+}
+
+#[derive(Debug)]
+pub struct WebdevError {
+    kind: WebdevErrorKind,
+}
+
+impl WebdevError {
+    pub fn new(kind: WebdevErrorKind) -> WebdevError {
+        WebdevError { kind }
+    }
+
+    pub fn kind(&self) -> &WebdevErrorKind {
+        &self.kind
+    }
+
+    pub fn to_rouille(&self) -> rouille::Response {
+        match self.kind {
+            WebdevErrorKind::NotFound => rouille::Response::empty_404(),
+            WebdevErrorKind::Format => rouille::Response::text("Bad request").with_status_code(400),
+            WebdevErrorKind::Forbidden => rouille::Response::text("Forbidden").with_status_code(403),
+            WebdevErrorKind::DatabaseError => rouille::Response::text("Internal server error").with_status_code(500),
+        }
+    }
+}
+
+impl fmt::Display for WebdevError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WebdevError: {:?}", self.kind)
+    }
+}
+
+impl std::error::Error for WebdevError {}
+
+impl From<serde_json::Error> for WebdevError {
+    fn from(_error: serde_json::Error) -> WebdevError {
+        WebdevError::new(WebdevErrorKind::Format)
+    }
+}
+
+impl From<diesel::result::Error> for WebdevError {
+    fn from(_error: diesel::result::Error) -> WebdevError {
+        WebdevError::new(WebdevErrorKind::DatabaseError)
+    }
+}